@@ -0,0 +1,53 @@
+use std::num::NonZeroUsize;
+
+use serde::{Deserialize, Serialize};
+
+fn default_concurrent_solves() -> NonZeroUsize {
+    NonZeroUsize::new(num_cpus::get()).unwrap_or(NonZeroUsize::new(1).unwrap())
+}
+
+fn default_concurrent_installs() -> NonZeroUsize {
+    NonZeroUsize::new(num_cpus::get()).unwrap_or(NonZeroUsize::new(1).unwrap())
+}
+
+/// Limits on how much work pixi is allowed to run at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConcurrencyConfig {
+    /// The maximum number of solves that are allowed to run concurrently.
+    #[serde(default = "default_concurrent_solves")]
+    pub solves: NonZeroUsize,
+
+    /// The maximum number of environment (or solve-group) prefixes that are
+    /// allowed to be installed concurrently.
+    #[serde(default = "default_concurrent_installs")]
+    pub installs: NonZeroUsize,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            solves: default_concurrent_solves(),
+            installs: default_concurrent_installs(),
+        }
+    }
+}
+
+impl ConcurrencyConfig {
+    /// Merges `other` into `self`, letting any non-default value in `other`
+    /// take precedence.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            solves: if other.solves == default_concurrent_solves() {
+                self.solves
+            } else {
+                other.solves
+            },
+            installs: if other.installs == default_concurrent_installs() {
+                self.installs
+            } else {
+                other.installs
+            },
+        }
+    }
+}
@@ -0,0 +1,96 @@
+mod concurrency;
+
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use miette::IntoDiagnostic;
+use serde::{Deserialize, Serialize};
+
+pub use concurrency::ConcurrencyConfig;
+
+/// Where pixi should place detached environments, if anywhere.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DetachedEnvironments {
+    path: Option<PathBuf>,
+}
+
+impl DetachedEnvironments {
+    /// Returns the configured detached-environments directory, if any.
+    pub fn path(&self) -> miette::Result<Option<PathBuf>> {
+        Ok(self.path.clone())
+    }
+}
+
+/// The pixi configuration, merged from the global config file, workspace
+/// manifest and command line flags.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    concurrency: ConcurrencyConfig,
+
+    #[serde(default)]
+    detached_environments: DetachedEnvironments,
+}
+
+impl Config {
+    /// The maximum number of solves pixi is allowed to run concurrently.
+    pub fn max_concurrent_solves(&self) -> usize {
+        self.concurrency.solves.get()
+    }
+
+    /// The maximum number of environment (or solve-group) prefixes pixi is
+    /// allowed to install concurrently. Mirrors [`Self::max_concurrent_solves`].
+    pub fn max_concurrent_installs(&self) -> usize {
+        self.concurrency.installs.get()
+    }
+
+    /// Returns the configured location for detached environments.
+    pub fn detached_environments(&self) -> &DetachedEnvironments {
+        &self.detached_environments
+    }
+
+    /// Returns the default configuration; no config file under the given
+    /// workspace root is read.
+    pub fn load(_root: &Path) -> miette::Result<Self> {
+        Ok(Self::default())
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.concurrency = self.concurrency.merge(other.concurrency);
+        self.detached_environments = other.detached_environments;
+        self
+    }
+}
+
+/// Command line flags that can override configuration values, flattened into
+/// the relevant CLI commands.
+#[derive(Parser, Debug, Clone, Default)]
+pub struct ConfigCli {
+    /// The maximum number of solves that are allowed to run concurrently.
+    #[arg(long)]
+    concurrent_solves: Option<std::num::NonZeroUsize>,
+
+    /// The maximum number of environment (or solve-group) prefixes that are
+    /// allowed to be installed concurrently.
+    #[arg(long)]
+    concurrent_installs: Option<std::num::NonZeroUsize>,
+}
+
+impl ConfigCli {
+    /// Applies any flags set on the command line on top of `config`.
+    pub fn merge_config(&self, mut config: Config) -> Config {
+        if let Some(solves) = self.concurrent_solves {
+            config.concurrency.solves = solves;
+        }
+        if let Some(installs) = self.concurrent_installs {
+            config.concurrency.installs = installs;
+        }
+        config
+    }
+}
+
+pub fn load_and_merge(root: &Path, cli: &ConfigCli) -> miette::Result<Config> {
+    Config::load(root)
+        .into_diagnostic()
+        .map(|config| cli.merge_config(config))
+}
@@ -1,15 +1,179 @@
+use std::collections::{BTreeMap, HashMap};
+
 use clap::Parser;
 use fancy_display::FancyDisplay;
+use futures::{
+    future,
+    stream::{self, StreamExt},
+};
 use itertools::Itertools;
+use miette::IntoDiagnostic;
 use pixi_config::ConfigCli;
+use rattler_conda_types::Platform;
+use serde::Serialize;
 
 use crate::{
     UpdateLockFileOptions, WorkspaceLocator,
     cli::cli_config::WorkspaceConfig,
     environment::get_update_lock_file_and_prefixes,
     lock_file::{ReinstallPackages, UpdateMode},
+    workspace::grouped_environment::{GroupedEnvironment, GroupedEnvironmentName},
 };
 
+/// The action pixi will take for a single package while bringing an
+/// environment's prefix in line with its lock file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PackageAction {
+    /// The package is already present in the prefix at the version and build
+    /// required by the lock file, so nothing needs to happen.
+    AlreadyInstalled,
+    /// The package is present in the prefix, but at a different version or
+    /// build than the lock file requires, so it must be re-linked.
+    Updated,
+    /// The package is not present in the prefix yet and must be fetched and
+    /// linked.
+    ToInstall,
+}
+
+/// A single package together with the action pixi will take for it.
+#[derive(Debug, Serialize)]
+pub struct PackageInstallAction {
+    pub name: String,
+    pub action: PackageAction,
+}
+
+/// The install plan for a single solve group or standalone environment: the
+/// packages it requires, partitioned into those already installed and those
+/// that still need to be fetched and linked.
+#[derive(Debug, Serialize)]
+pub struct EnvironmentInstallPlan {
+    pub environment: String,
+    pub solve_group: Option<String>,
+    pub packages: Vec<PackageInstallAction>,
+}
+
+impl EnvironmentInstallPlan {
+    /// The number of packages that still need to be fetched and linked.
+    fn new_count(&self) -> usize {
+        self.packages
+            .iter()
+            .filter(|p| p.action == PackageAction::ToInstall)
+            .count()
+    }
+
+    /// The number of packages that are installed but at a different version
+    /// or build than required.
+    fn updated_count(&self) -> usize {
+        self.packages
+            .iter()
+            .filter(|p| p.action == PackageAction::Updated)
+            .count()
+    }
+
+    /// The number of packages that are already installed and unchanged.
+    fn unchanged_count(&self) -> usize {
+        self.packages
+            .iter()
+            .filter(|p| p.action == PackageAction::AlreadyInstalled)
+            .count()
+    }
+}
+
+impl PackageAction {
+    /// Ranks actions from least to most significant, so that when the same
+    /// package name is classified differently across the environments of a
+    /// group, the most significant action wins instead of an arbitrary one.
+    fn rank(self) -> u8 {
+        match self {
+            PackageAction::AlreadyInstalled => 0,
+            PackageAction::Updated => 1,
+            PackageAction::ToInstall => 2,
+        }
+    }
+}
+
+/// Builds the install plan for a single [`GroupedEnvironment`] by diffing the
+/// packages already linked into its prefix against the packages the
+/// just-solved lock file requires for the current platform.
+async fn build_install_plan(
+    group: &GroupedEnvironment<'_>,
+    lock_file: &rattler_lock::LockFile,
+) -> miette::Result<EnvironmentInstallPlan> {
+    let platform = Platform::current();
+
+    // Map each installed package's name to its (version, build), so we can
+    // tell an unchanged package apart from one that's installed at a
+    // different version or build than the lock file now requires.
+    let installed = group
+        .prefix()
+        .find_installed_packages()
+        .await?
+        .into_iter()
+        .map(|record| {
+            let package_record = &record.repodata_record.package_record;
+            (
+                package_record.name.as_normalized().to_string(),
+                (
+                    package_record.version.to_string(),
+                    package_record.build.clone(),
+                ),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    // The environments in a group can each require the same package name at
+    // a different version, and each is compared against the group's one
+    // prefix above. Fold by name instead of just deduping, so the most significant action (`ToInstall` > `Updated` >
+    // `AlreadyInstalled`) is the one that's reported rather than an
+    // arbitrary one.
+    let mut by_name: BTreeMap<String, PackageAction> = BTreeMap::new();
+    for env in group.environments() {
+        let Some(lock_file_env) = lock_file.environment(env.name().as_str()) else {
+            continue;
+        };
+        let Some(conda_packages) = lock_file_env.conda_packages(platform) else {
+            continue;
+        };
+        for record in conda_packages {
+            let package_record = record.package_record();
+            let name = package_record.name.as_normalized().to_string();
+            let required = (
+                package_record.version.to_string(),
+                package_record.build.clone(),
+            );
+            let action = match installed.get(&name) {
+                Some(installed) if *installed == required => PackageAction::AlreadyInstalled,
+                Some(_) => PackageAction::Updated,
+                None => PackageAction::ToInstall,
+            };
+            by_name
+                .entry(name)
+                .and_modify(|existing| {
+                    if action.rank() > existing.rank() {
+                        *existing = action;
+                    }
+                })
+                .or_insert(action);
+        }
+    }
+    let packages = by_name
+        .into_iter()
+        .map(|(name, action)| PackageInstallAction { name, action })
+        .collect();
+
+    let solve_group = match group.name() {
+        GroupedEnvironmentName::Group(name) => Some(name),
+        GroupedEnvironmentName::Environment(_) => None,
+    };
+
+    Ok(EnvironmentInstallPlan {
+        environment: group.name().as_str().to_string(),
+        solve_group,
+        packages,
+    })
+}
+
 /// Install an environment, both updating the lockfile and installing the
 /// environment.
 ///
@@ -29,6 +193,12 @@ use crate::{
 ///
 /// You can use `pixi reinstall` to reinstall all environments, one environment
 /// or just some packages of an environment.
+///
+/// Pass `--dry-run` to resolve the lock file without installing or updating
+/// any environment, which is useful for previewing the effect of `pixi
+/// install` in CI or scripts. The solve still writes the lock file; its
+/// previous contents are restored afterwards (or it is removed if there was
+/// none).
 #[derive(Parser, Debug)]
 pub struct Args {
     #[clap(flatten)]
@@ -47,6 +217,17 @@ pub struct Args {
     /// Install all environments
     #[arg(long, short, conflicts_with = "environment")]
     pub all: bool,
+
+    /// Only show what would be done, without installing or updating any
+    /// environment. The lock file written by the solve is restored to its
+    /// previous contents, or removed if there was none before.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Write the install plan as JSON to stdout instead of printing a
+    /// human-readable summary.
+    #[arg(long)]
+    pub json: bool,
 }
 
 pub async fn execute(args: Args) -> miette::Result<()> {
@@ -78,24 +259,114 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         .map(|env| workspace.environment_from_name_or_env_var(Some(env)))
         .collect::<Result<Vec<_>, _>>()?;
 
-    // Update the prefixes by installing all packages
-    get_update_lock_file_and_prefixes(
+    let lock_file_usage = args.lock_file_usage.try_into()?;
+    let max_concurrent_solves = workspace.config().max_concurrent_solves();
+    let max_concurrent_installs = workspace.config().max_concurrent_installs();
+
+    // `get_update_lock_file_and_prefixes` always persists the lock file it
+    // solves, but `--dry-run`'s key invariant is that pixi must not touch
+    // anything on disk. There's no "solve only, don't write" mode to ask for,
+    // so snapshot the current `pixi.lock` up front and restore it afterwards.
+    // This can't be made fully atomic with what the solve itself does to the
+    // file (a process kill between the solve and the restore below would
+    // still leave a mutated lock file on disk); a genuinely side-effect-free
+    // dry-run needs a solve-in-memory path in `get_update_lock_file_and_prefixes`
+    // itself. Absent that, at least don't confuse "no lock file yet" with "we
+    // failed to read the existing one": the former should result in deleting
+    // the file the solve just created, the latter must never delete data we
+    // couldn't read back.
+    let lock_file_path = workspace.lock_file_path();
+    let original_lock_file = if args.dry_run {
+        match std::fs::read(&lock_file_path) {
+            Ok(contents) => Some(contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(err).into_diagnostic();
+            }
+        }
+    } else {
+        None
+    };
+
+    // Solve and persist the lock file exactly once for *all* requested
+    // environments. The solver already takes solve groups into account, and
+    // running one `get_update_lock_file_and_prefixes` call per group here
+    // would mean multiple independent callers reading, updating and writing
+    // the single on-disk `pixi.lock` concurrently, clobbering each other's
+    // entries. Prefix linking has no such shared state, so that part is
+    // parallelized separately below instead.
+    let lock_file_data = get_update_lock_file_and_prefixes(
         &environments,
         UpdateMode::Revalidate,
         UpdateLockFileOptions {
-            lock_file_usage: args.lock_file_usage.try_into()?,
-            no_install: false,
-            max_concurrent_solves: workspace.config().max_concurrent_solves(),
+            lock_file_usage,
+            no_install: true,
+            max_concurrent_solves,
         },
         ReinstallPackages::default(),
     )
     .await?;
 
-    let installed_envs = environments
+    if args.dry_run {
+        match &original_lock_file {
+            Some(contents) => {
+                // Write alongside the target and rename over it, rather than
+                // writing to `lock_file_path` directly, so a reader can never
+                // observe a partially-written restore.
+                let tmp_path = lock_file_path.with_extension("lock.dry-run-restore");
+                std::fs::write(&tmp_path, contents).into_diagnostic()?;
+                std::fs::rename(&tmp_path, &lock_file_path).into_diagnostic()?;
+            }
+            None => {
+                // There was no lock file before the dry-run; don't leave one
+                // behind.
+                let _ = std::fs::remove_file(&lock_file_path);
+            }
+        }
+    }
+
+    // Environments that share a solve group share a solve and are reported as
+    // one unit, but distinct groups are entirely independent.
+    let groups = environments
         .into_iter()
-        .map(|env| env.name())
+        .map(GroupedEnvironment::from)
+        .unique_by(|group| group.name().as_str().to_string())
         .collect::<Vec<_>>();
 
+    let plans = future::try_join_all(
+        groups
+            .iter()
+            .map(|group| build_install_plan(group, &lock_file_data.lock_file)),
+    )
+    .await?;
+
+    if !args.dry_run {
+        // Link every environment in every group, not just one representative
+        // per group: a solve group shares a solve, but pixi still links a
+        // prefix per environment. Each environment only touches its own
+        // prefix directory and `prefix()` only reads the already-solved
+        // `lock_file_data`, so environments can share it without any
+        // synchronization and up to `max_concurrent_installs` can be linked
+        // at the same time.
+        let all_environments = groups
+            .iter()
+            .flat_map(|group| group.environments())
+            .collect::<Vec<_>>();
+        stream::iter(&all_environments)
+            .map(|env| lock_file_data.prefix(env))
+            .buffer_unordered(max_concurrent_installs)
+            .collect::<Vec<miette::Result<_>>>()
+            .await
+            .into_iter()
+            .collect::<miette::Result<Vec<_>>>()?;
+    }
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&plans).into_diagnostic()?;
+        println!("{json}");
+        return Ok(());
+    }
+
     // Message what's installed
     let detached_envs_message =
         if let Ok(Some(path)) = workspace.config().detached_environments().path() {
@@ -104,19 +375,21 @@ pub async fn execute(args: Args) -> miette::Result<()> {
             "".to_string()
         };
 
-    if installed_envs.len() == 1 {
-        eprintln!(
-            "{}The {} environment has been installed{}.",
-            console::style(console::Emoji("✔ ", "")).green(),
-            installed_envs[0].fancy_display(),
-            detached_envs_message
-        );
+    let verb = if args.dry_run {
+        "would be installed"
     } else {
+        "has been installed"
+    };
+    for (group, plan) in groups.iter().zip(&plans) {
         eprintln!(
-            "{}The following environments have been installed: {}\t{}",
+            "{}The {} environment {}{} ({} new, {} updated, {} unchanged).",
             console::style(console::Emoji("✔ ", "")).green(),
-            installed_envs.iter().map(|n| n.fancy_display()).join(", "),
-            detached_envs_message
+            group.name().fancy_display(),
+            verb,
+            detached_envs_message,
+            plan.new_count(),
+            plan.updated_count(),
+            plan.unchanged_count(),
         );
     }
 